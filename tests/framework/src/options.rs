@@ -23,8 +23,11 @@ pub struct TestOptions {
     pub output_path: PathBuf,
     pub sleep_to_meet_frame_rate: bool,
     pub image_comparisons: HashMap<String, ImageComparison>,
+    pub video_comparison: Option<VideoComparison>,
     pub ignore: bool,
     pub known_failure: bool,
+    ignore_condition: Condition,
+    known_failure_condition: Condition,
     pub approximations: Option<Approximations>,
     pub player_options: PlayerOptions,
     pub log_fetch: bool,
@@ -40,8 +43,11 @@ impl Default for TestOptions {
             output_path: PathBuf::from("output.txt"),
             sleep_to_meet_frame_rate: false,
             image_comparisons: Default::default(),
+            video_comparison: None,
             ignore: false,
             known_failure: false,
+            ignore_condition: Condition::default(),
+            known_failure_condition: Condition::default(),
             approximations: None,
             player_options: PlayerOptions::default(),
             log_fetch: false,
@@ -78,6 +84,71 @@ impl TestOptions {
     pub fn output_path(&self, test_directory: &Path) -> PathBuf {
         test_directory.join(&self.output_path)
     }
+
+    /// Whether this test should be ignored on `environment_name` (eg. `"wgpu-warp"`).
+    ///
+    /// True if the unconditional `ignore` flag is set, or if `ignore_condition` applies
+    /// to `environment_name`.
+    pub fn is_ignored(&self, environment_name: &str) -> bool {
+        self.ignore || self.ignore_condition.applies(environment_name)
+    }
+
+    /// Whether this test is a known failure on `environment_name` (eg. `"wgpu-warp"`).
+    ///
+    /// True if the unconditional `known_failure` flag is set, or if
+    /// `known_failure_condition` applies to `environment_name`.
+    pub fn is_known_failure(&self, environment_name: &str) -> bool {
+        self.known_failure || self.known_failure_condition.applies(environment_name)
+    }
+}
+
+/// A plain `true`/`false`, or a conditional form that only applies on specific platforms
+/// and/or renderers, eg. `ignore_condition = { platforms = ["windows"], renderers = ["warp"] }`.
+/// An empty `platforms`/`renderers` list matches every platform/renderer.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Condition {
+    Bool(bool),
+    Conditional {
+        #[serde(default)]
+        platforms: Vec<String>,
+        #[serde(default)]
+        renderers: Vec<String>,
+    },
+}
+
+impl Default for Condition {
+    fn default() -> Self {
+        Condition::Bool(false)
+    }
+}
+
+impl Condition {
+    fn applies(&self, environment_name: &str) -> bool {
+        match self {
+            Condition::Bool(value) => *value,
+            Condition::Conditional {
+                platforms,
+                renderers,
+            } => {
+                let platform_matches = platforms.is_empty()
+                    || platforms
+                        .iter()
+                        .any(|platform| platform.eq_ignore_ascii_case(std::env::consts::OS));
+                let renderer_matches = renderers.is_empty()
+                    || renderers.iter().any(|renderer| {
+                        environment_name
+                            .rsplit('-')
+                            .next()
+                            .is_some_and(|actual_renderer| {
+                                actual_renderer.eq_ignore_ascii_case(renderer)
+                            })
+                    });
+
+                platform_matches && renderer_matches
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Default)]
@@ -150,6 +221,11 @@ impl PlayerOptions {
                 2 => StageQuality::Medium,
                 _ => StageQuality::Low,
             });
+
+            player_builder = player_builder
+                .with_disable_aa(render_options.disable_aa)
+                .with_disable_subpixel_text(render_options.disable_subpixel_text)
+                .with_allow_mipmaps(render_options.allow_mipmaps);
         }
 
         if self.with_audio {
@@ -198,18 +274,250 @@ impl PlayerOptions {
     }
 }
 
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageComparisonOp {
+    /// The actual and expected images must match within `tolerance`/`max_outliers`.
+    #[default]
+    Equal,
+
+    /// The actual and expected images must *not* match within `tolerance`/`max_outliers`.
+    ///
+    /// Useful for regression tests that prove a rendering feature actually changes the
+    /// output, rather than silently becoming a no-op.
+    NotEqual,
+}
+
+/// A rectangular region of an image that is allowed its own fuzzy tolerance,
+/// independent of the global `tolerance`/`max_outliers` of the [`ImageComparison`] it
+/// belongs to. Useful for known-noisy areas (text AA edges, gradient banding) that
+/// would otherwise force loosening the comparison for the whole frame.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct FuzzyRegion {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    tolerance: u8,
+    max_outliers: usize,
+}
+
+impl FuzzyRegion {
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
 #[derive(Deserialize, Default, Clone, Debug)]
 #[serde(default, deny_unknown_fields)]
 pub struct ImageComparison {
     tolerance: u8,
     max_outliers: usize,
     pub trigger: ImageTrigger,
+    op: ImageComparisonOp,
+    regions: Vec<FuzzyRegion>,
 }
 
 fn calc_difference(lhs: u8, rhs: u8) -> u8 {
     (lhs as i16 - rhs as i16).unsigned_abs() as u8
 }
 
+/// Whether failing image comparisons should also be previewed inline in the terminal,
+/// in addition to being written to disk as usual.
+fn inline_images_enabled() -> bool {
+    std::env::var_os("RUFFLE_TEST_INLINE_IMAGES").is_some()
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied().unwrap_or(0);
+        let b2 = chunk.get(2).copied().unwrap_or(0);
+
+        out.push(ALPHABET[(b0 >> 2) as usize] as char);
+        out.push(ALPHABET[(((b0 & 0x03) << 4) | (b1 >> 4)) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(((b1 & 0x0f) << 2) | (b2 >> 6)) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[(b2 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+/// Prints `image` directly into the terminal using the Kitty graphics protocol.
+fn print_image_kitty(image: &image::RgbaImage) {
+    let (width, height) = image.dimensions();
+    let payload = base64_encode(image.as_raw());
+    let chunks: Vec<&[u8]> = payload.as_bytes().chunks(4096).collect();
+
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = (i + 1 < chunks.len()) as u8;
+        let chunk = std::str::from_utf8(chunk).expect("base64 output is always ASCII");
+        if i == 0 {
+            print!("\x1b_Ga=T,f=32,s={width},v={height},m={more};{chunk}\x1b\\");
+        } else {
+            print!("\x1b_Gm={more};{chunk}\x1b\\");
+        }
+    }
+    println!();
+}
+
+/// Prints `image` directly into the terminal using the sixel protocol, quantizing
+/// colors into the classic 6x6x6 xterm color cube so no palette negotiation is needed.
+fn print_image_sixel(image: &image::RgbaImage) {
+    let (width, height) = image.dimensions();
+
+    let mut palette: Vec<(u8, u8, u8)> = Vec::new();
+    let mut palette_index = HashMap::new();
+    let indices: Vec<usize> = image
+        .pixels()
+        .map(|pixel| {
+            let [r, g, b, _] = pixel.0;
+            let key = (r / 43, g / 43, b / 43);
+            *palette_index.entry(key).or_insert_with(|| {
+                palette.push(key);
+                palette.len() - 1
+            })
+        })
+        .collect();
+
+    let mut out = String::from("\x1bPq");
+    for (index, (r, g, b)) in palette.iter().enumerate() {
+        out.push_str(&format!(
+            "#{index};2;{};{};{}",
+            *r as u32 * 100 / 5,
+            *g as u32 * 100 / 5,
+            *b as u32 * 100 / 5
+        ));
+    }
+
+    for band_start in (0..height).step_by(6) {
+        let band_height = (height - band_start).min(6);
+
+        // Bucket every pixel in this band by palette color in a single pass, instead of
+        // rescanning the whole band once per distinct color.
+        let mut sixels = vec![0u8; width as usize * palette.len()];
+        let mut color_present = vec![false; palette.len()];
+        for row in 0..band_height {
+            let y = band_start + row;
+            for x in 0..width {
+                let color = indices[(y * width + x) as usize];
+                sixels[color * width as usize + x as usize] |= 1 << row;
+                color_present[color] = true;
+            }
+        }
+
+        for color in 0..palette.len() {
+            if !color_present[color] {
+                continue;
+            }
+            out.push_str(&format!("#{color}"));
+            for x in 0..width as usize {
+                out.push((sixels[color * width as usize + x] + 0x3f) as char);
+            }
+            out.push('$');
+        }
+        out.push('-');
+    }
+    out.push_str("\x1b\\");
+
+    print!("{out}");
+    println!();
+}
+
+/// Previews `image` inline in the terminal, auto-selecting Kitty or sixel based on
+/// the environment, and doing nothing on terminals that support neither.
+fn print_image_inline(image: &image::RgbaImage) {
+    let is_kitty = std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || std::env::var("TERM")
+            .map(|term| term.contains("kitty"))
+            .unwrap_or(false);
+
+    if is_kitty {
+        print_image_kitty(image);
+    } else if std::env::var("TERM")
+        .map(|term| term.contains("xterm") || term.contains("mlterm") || term.contains("sixel"))
+        .unwrap_or(false)
+    {
+        print_image_sixel(image);
+    }
+    // Unsupported terminals: do nothing, the PNGs on disk are still the source of truth.
+}
+
+fn rgb_to_rgba(width: u32, height: u32, rgb: &[u8]) -> image::RgbaImage {
+    let mut rgba = Vec::with_capacity(rgb.len() / 3 * 4);
+    for pixel in rgb.chunks_exact(3) {
+        rgba.extend_from_slice(pixel);
+        rgba.push(255);
+    }
+    image::RgbaImage::from_raw(width, height, rgba).expect("rgba buffer is sized from rgb buffer")
+}
+
+/// The per-channel differences between two equally-sized images, shared by
+/// [`ImageComparison`] and [`VideoComparison`].
+struct FrameDifference {
+    data: Vec<u8>,
+    max_difference: u8,
+    is_alpha_different: bool,
+}
+
+fn diff_frames(expected: &image::RgbaImage, actual: &image::RgbaImage) -> FrameDifference {
+    let mut is_alpha_different = false;
+
+    let data: Vec<u8> = expected
+        .as_raw()
+        .chunks_exact(4)
+        .zip(actual.as_raw().chunks_exact(4))
+        .flat_map(|(cmp_chunk, data_chunk)| {
+            if cmp_chunk[3] != data_chunk[3] {
+                is_alpha_different = true;
+            }
+
+            [
+                calc_difference(cmp_chunk[0], data_chunk[0]),
+                calc_difference(cmp_chunk[1], data_chunk[1]),
+                calc_difference(cmp_chunk[2], data_chunk[2]),
+                calc_difference(cmp_chunk[3], data_chunk[3]),
+            ]
+        })
+        .collect();
+
+    let max_difference = data
+        .chunks_exact(4)
+        .map(|colors| colors[0].max(colors[1]).max(colors[2]).max(colors[3]))
+        .max()
+        .unwrap();
+
+    FrameDifference {
+        data,
+        max_difference,
+        is_alpha_different,
+    }
+}
+
+fn count_outliers(difference_data: &[u8], tolerance: u8) -> usize {
+    difference_data
+        .chunks_exact(4)
+        .map(|colors| {
+            (colors[0] > tolerance) as usize
+                + (colors[1] > tolerance) as usize
+                + (colors[2] > tolerance) as usize
+                + (colors[3] > tolerance) as usize
+        })
+        .sum()
+}
+
 impl ImageComparison {
     pub fn test(
         &self,
@@ -247,43 +555,78 @@ impl ImageComparison {
             ));
         }
 
-        let mut is_alpha_different = false;
+        let FrameDifference {
+            data: difference_data,
+            max_difference,
+            is_alpha_different,
+        } = diff_frames(&expected_image, &actual_image);
 
-        let difference_data: Vec<u8> = expected_image
-            .as_raw()
-            .chunks_exact(4)
-            .zip(actual_image.as_raw().chunks_exact(4))
-            .flat_map(|(cmp_chunk, data_chunk)| {
-                if cmp_chunk[3] != data_chunk[3] {
-                    is_alpha_different = true;
-                }
+        let width = actual_image.width();
+
+        // Attribute each pixel's outliers to the first region containing it, falling
+        // back to the global tolerance/max_outliers budget for everything else.
+        let mut region_outliers = vec![0usize; self.regions.len()];
+        let mut global_outliers = 0usize;
+
+        for (i, colors) in difference_data.chunks_exact(4).enumerate() {
+            let x = i as u32 % width;
+            let y = i as u32 / width;
 
-                [
-                    calc_difference(cmp_chunk[0], data_chunk[0]),
-                    calc_difference(cmp_chunk[1], data_chunk[1]),
-                    calc_difference(cmp_chunk[2], data_chunk[2]),
-                    calc_difference(cmp_chunk[3], data_chunk[3]),
-                ]
+            let region_index = self.regions.iter().position(|region| region.contains(x, y));
+            let tolerance = region_index
+                .map(|index| self.regions[index].tolerance)
+                .unwrap_or(self.tolerance);
+
+            let pixel_outliers = (colors[0] > tolerance) as usize
+                + (colors[1] > tolerance) as usize
+                + (colors[2] > tolerance) as usize
+                + (colors[3] > tolerance) as usize;
+
+            match region_index {
+                Some(index) => region_outliers[index] += pixel_outliers,
+                None => global_outliers += pixel_outliers,
+            }
+        }
+
+        let outliers = global_outliers + region_outliers.iter().sum::<usize>();
+
+        let mut budget_failures: Vec<String> = self
+            .regions
+            .iter()
+            .zip(region_outliers.iter())
+            .enumerate()
+            .filter(|(_, (region, outliers))| **outliers > region.max_outliers)
+            .map(|(index, (region, outliers))| {
+                format!(
+                    "region {} ({}x{} at {},{}) had {} outliers, {} over its limit of {}",
+                    index,
+                    region.width,
+                    region.height,
+                    region.x,
+                    region.y,
+                    outliers,
+                    outliers - region.max_outliers,
+                    region.max_outliers
+                )
             })
             .collect();
 
-        let outliers: usize = difference_data
-            .chunks_exact(4)
-            .map(|colors| {
-                (colors[0] > self.tolerance) as usize
-                    + (colors[1] > self.tolerance) as usize
-                    + (colors[2] > self.tolerance) as usize
-                    + (colors[3] > self.tolerance) as usize
-            })
-            .sum();
+        if global_outliers > self.max_outliers {
+            budget_failures.push(format!(
+                "the rest of the image had {} outliers, {} over its limit of {}",
+                global_outliers,
+                global_outliers - self.max_outliers,
+                self.max_outliers
+            ));
+        }
 
-        let max_difference = difference_data
-            .chunks_exact(4)
-            .map(|colors| colors[0].max(colors[1]).max(colors[2]).max(colors[3]))
-            .max()
-            .unwrap();
+        let outliers_exceeded = !budget_failures.is_empty();
+        let failed = match self.op {
+            ImageComparisonOp::Equal => outliers_exceeded,
+            ImageComparisonOp::NotEqual => !outliers_exceeded,
+        };
 
-        if outliers > self.max_outliers {
+        if failed {
             save_actual_image()?;
 
             let mut difference_color = Vec::with_capacity(
@@ -293,6 +636,17 @@ impl ImageComparison {
                 difference_color.extend_from_slice(&p[..3]);
             }
 
+            if inline_images_enabled() {
+                println!("Actual image for '{name}':");
+                print_image_inline(&actual_image);
+                println!("Color difference for '{name}':");
+                print_image_inline(&rgb_to_rgba(
+                    actual_image.width(),
+                    actual_image.height(),
+                    &difference_color,
+                ));
+            }
+
             if !known_failure {
                 // If we're expecting failure, spamming files isn't productive.
                 image::RgbImage::from_raw(
@@ -326,27 +680,212 @@ impl ImageComparison {
                 }
             }
 
+            return Err(match self.op {
+                ImageComparisonOp::Equal => anyhow!(
+                    "Image '{}' failed: {}. Max difference is {}",
+                    name,
+                    budget_failures.join("; "),
+                    max_difference
+                ),
+                ImageComparisonOp::NotEqual => anyhow!(
+                    "Image '{}' failed: expected images to differ but they matched within tolerance ({} outliers outside any region, allowed limit of {})",
+                    name,
+                    global_outliers,
+                    self.max_outliers
+                ),
+            });
+        } else {
+            println!("Image '{name}' succeeded: {outliers} outliers found, max difference {max_difference}",);
+        }
+
+        Ok(())
+    }
+}
+
+/// Compares the rendered frame at every tick over `start_frame..=end_frame` against a
+/// directory of numbered reference PNGs (`reference_path/{frame:04}.png`), instead of a
+/// single snapshot. Useful for animation/timing regressions that only show up across
+/// several frames.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct VideoComparison {
+    pub start_frame: u32,
+    pub end_frame: u32,
+    pub reference_path: PathBuf,
+    tolerance: u8,
+    max_outliers: usize,
+    pub encode_video: bool,
+}
+
+impl VideoComparison {
+    pub fn test(&self, name: &str, frames: &[image::RgbaImage], test_path: &Path) -> Result<()> {
+        use anyhow::Context;
+
+        let mut worst_frame = None;
+        let mut worst_outliers = 0usize;
+        let mut total_outliers = 0usize;
+        let mut first_diverging_frame = None;
+        let mut expected_frames = Vec::with_capacity(frames.len());
+
+        for (i, actual) in frames.iter().enumerate() {
+            let frame_number = self.start_frame + i as u32;
+            let expected_path = test_path
+                .join(&self.reference_path)
+                .join(format!("{frame_number:04}.png"));
+            let expected = image::open(&expected_path)
+                .with_context(|| format!("Couldn't open reference frame {frame_number}"))?
+                .to_rgba8();
+
+            let difference = diff_frames(&expected, actual);
+            let outliers = count_outliers(&difference.data, self.tolerance);
+
+            if outliers > self.max_outliers && first_diverging_frame.is_none() {
+                first_diverging_frame = Some(frame_number);
+            }
+
+            if outliers > worst_outliers {
+                worst_outliers = outliers;
+                worst_frame = Some(frame_number);
+            }
+
+            total_outliers += outliers;
+            expected_frames.push(expected);
+        }
+
+        let mean_outliers = total_outliers as f64 / frames.len().max(1) as f64;
+
+        println!(
+            "Video '{name}': mean {mean_outliers:.2} outliers/frame, worst frame {worst_frame:?} ({worst_outliers} outliers)",
+        );
+
+        #[cfg(feature = "video_comparison_mp4")]
+        if self.encode_video {
+            encode_comparison_video(name, test_path, self.start_frame, frames, &expected_frames)?;
+        }
+
+        if let Some(frame) = first_diverging_frame {
             return Err(anyhow!(
-                "Image '{}' failed: Number of outliers ({}) is bigger than allowed limit of {}. Max difference is {}",
+                "Video '{}' failed: frame {} is the first to diverge more than {} outliers (worst frame {:?} with {} outliers, mean {:.2} outliers/frame)",
                 name,
-                outliers,
+                frame,
                 self.max_outliers,
-                max_difference
+                worst_frame,
+                worst_outliers,
+                mean_outliers
             ));
-        } else {
-            println!("Image '{name}' succeeded: {outliers} outliers found, max difference {max_difference}",);
         }
 
         Ok(())
     }
 }
 
+/// Encodes the captured frame sequence, side by side with the reference frames, into an
+/// mp4 so a failing [`VideoComparison`] can be reviewed as a video instead of a pile of PNGs.
+#[cfg(feature = "video_comparison_mp4")]
+fn encode_comparison_video(
+    name: &str,
+    test_path: &Path,
+    start_frame: u32,
+    actual_frames: &[image::RgbaImage],
+    expected_frames: &[image::RgbaImage],
+) -> Result<()> {
+    use anyhow::Context;
+    use ffmpeg_next as ffmpeg;
+
+    ffmpeg::init().context("Couldn't initialize ffmpeg")?;
+
+    let output_path = test_path.join(format!("{name}.comparison.mp4"));
+    let mut output =
+        ffmpeg::format::output(&output_path).context("Couldn't create mp4 output")?;
+
+    let codec = ffmpeg::encoder::find(ffmpeg::codec::Id::H264)
+        .context("No H.264 encoder available")?;
+    let mut stream = output
+        .add_stream(codec)
+        .context("Couldn't add video stream")?;
+    let context = ffmpeg::codec::context::Context::from_parameters(stream.parameters())?;
+    let mut encoder = context.encoder().video()?;
+
+    let (width, height) = actual_frames
+        .first()
+        .map(|frame| (frame.width() * 2, frame.height()))
+        .unwrap_or_default();
+    encoder.set_width(width);
+    encoder.set_height(height);
+    encoder.set_format(ffmpeg::format::Pixel::YUV420P);
+    encoder.set_time_base((1, 60));
+
+    let mut encoder = encoder.open_as(codec)?;
+    stream.set_parameters(&encoder);
+
+    output
+        .write_header()
+        .context("Couldn't write mp4 header")?;
+
+    let mut scaler = ffmpeg::software::scaling::Context::get(
+        ffmpeg::format::Pixel::RGBA,
+        width,
+        height,
+        ffmpeg::format::Pixel::YUV420P,
+        width,
+        height,
+        ffmpeg::software::scaling::Flags::BILINEAR,
+    )?;
+
+    for (frame_index, (actual, expected)) in actual_frames.iter().zip(expected_frames).enumerate()
+    {
+        let mut side_by_side =
+            image::RgbaImage::new(actual.width() * 2, actual.height());
+        image::imageops::replace(&mut side_by_side, actual, 0, 0);
+        image::imageops::replace(&mut side_by_side, expected, actual.width() as i64, 0);
+
+        let mut rgba_frame =
+            ffmpeg::util::frame::Video::new(ffmpeg::format::Pixel::RGBA, width, height);
+
+        // The frame's linesize may be padded past `width * 4` for alignment, so each row
+        // has to be copied individually rather than via one flat `copy_from_slice`.
+        let src_stride = (width * 4) as usize;
+        let dst_stride = rgba_frame.stride(0);
+        let src = side_by_side.as_raw();
+        let dst = rgba_frame.data_mut(0);
+        for row in 0..height as usize {
+            let src_row = &src[row * src_stride..(row + 1) * src_stride];
+            let dst_row = &mut dst[row * dst_stride..row * dst_stride + src_stride];
+            dst_row.copy_from_slice(src_row);
+        }
+
+        let mut yuv_frame = ffmpeg::util::frame::Video::empty();
+        scaler.run(&rgba_frame, &mut yuv_frame)?;
+        yuv_frame.set_pts(Some((start_frame as i64) + frame_index as i64));
+
+        encoder.send_frame(&yuv_frame)?;
+
+        let mut packet = ffmpeg::Packet::empty();
+        while encoder.receive_packet(&mut packet).is_ok() {
+            packet.write_interleaved(&mut output)?;
+        }
+    }
+
+    encoder.send_eof()?;
+    let mut packet = ffmpeg::Packet::empty();
+    while encoder.receive_packet(&mut packet).is_ok() {
+        packet.write_interleaved(&mut output)?;
+    }
+
+    output.write_trailer().context("Couldn't finalize mp4")?;
+
+    Ok(())
+}
+
 #[derive(Deserialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct RenderOptions {
     optional: bool,
     pub sample_count: u32,
     pub exclude_warp: bool,
+    pub disable_aa: bool,
+    pub disable_subpixel_text: bool,
+    pub allow_mipmaps: bool,
 }
 
 impl Default for RenderOptions {
@@ -355,6 +894,269 @@ impl Default for RenderOptions {
             optional: false,
             sample_count: 1,
             exclude_warp: false,
+            disable_aa: false,
+            disable_subpixel_text: false,
+            allow_mipmaps: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn condition_bool_ignores_environment() {
+        assert!(Condition::Bool(true).applies("wgpu-warp"));
+        assert!(!Condition::Bool(false).applies("wgpu-warp"));
+    }
+
+    #[test]
+    fn condition_renderer_matches_suffix_of_environment_name() {
+        let condition = Condition::Conditional {
+            platforms: vec![],
+            renderers: vec!["warp".to_string()],
+        };
+        assert!(condition.applies("wgpu-warp"));
+        assert!(condition.applies("WGPU-WARP"));
+        assert!(!condition.applies("wgpu-vulkan"));
+    }
+
+    #[test]
+    fn condition_empty_lists_match_everything() {
+        let condition = Condition::Conditional {
+            platforms: vec![],
+            renderers: vec![],
+        };
+        assert!(condition.applies("wgpu-warp"));
+    }
+
+    #[test]
+    fn fuzzy_region_contains_checks_bounds() {
+        let region = FuzzyRegion {
+            x: 10,
+            y: 10,
+            width: 5,
+            height: 5,
+            tolerance: 0,
+            max_outliers: 0,
+        };
+        assert!(region.contains(10, 10));
+        assert!(region.contains(14, 14));
+        assert!(!region.contains(15, 10));
+        assert!(!region.contains(9, 10));
+    }
+
+    #[test]
+    fn base64_encode_matches_known_vectors() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    fn solid_image(width: u32, height: u32, pixel: [u8; 4]) -> image::RgbaImage {
+        image::RgbaImage::from_raw(
+            width,
+            height,
+            pixel.repeat((width * height) as usize),
+        )
+        .unwrap()
+    }
+
+    // `test()` never touches disk on success, and skips writing diagnostic files entirely
+    // when `known_failure` is true, so a placeholder path is safe for these cases.
+    const NO_WRITES_PATH: &str = ".";
+
+    #[test]
+    fn image_comparison_equal_passes_within_tolerance() {
+        let comparison = ImageComparison::default();
+        let image = solid_image(2, 2, [0, 0, 0, 255]);
+
+        assert!(comparison
+            .test(
+                "test",
+                image.clone(),
+                image,
+                Path::new(NO_WRITES_PATH),
+                "wgpu-warp".to_string(),
+                false,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn image_comparison_equal_fails_when_outliers_exceed_budget() {
+        let comparison = ImageComparison::default();
+        let actual = solid_image(2, 2, [0, 0, 0, 255]);
+        let expected = solid_image(2, 2, [255, 255, 255, 255]);
+
+        let err = comparison
+            .test(
+                "test",
+                actual,
+                expected,
+                Path::new(NO_WRITES_PATH),
+                "wgpu-warp".to_string(),
+                true,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("the rest of the image had 12 outliers"));
+    }
+
+    #[test]
+    fn image_comparison_not_equal_passes_when_images_differ() {
+        let comparison = ImageComparison {
+            op: ImageComparisonOp::NotEqual,
+            ..Default::default()
+        };
+        let actual = solid_image(2, 2, [0, 0, 0, 255]);
+        let expected = solid_image(2, 2, [255, 255, 255, 255]);
+
+        assert!(comparison
+            .test(
+                "test",
+                actual,
+                expected,
+                Path::new(NO_WRITES_PATH),
+                "wgpu-warp".to_string(),
+                false,
+            )
+            .is_ok());
+    }
+
+    #[test]
+    fn image_comparison_not_equal_fails_when_images_match() {
+        let comparison = ImageComparison {
+            op: ImageComparisonOp::NotEqual,
+            ..Default::default()
+        };
+        let image = solid_image(2, 2, [0, 0, 0, 255]);
+
+        let err = comparison
+            .test(
+                "test",
+                image.clone(),
+                image,
+                Path::new(NO_WRITES_PATH),
+                "wgpu-warp".to_string(),
+                true,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("expected images to differ"));
+    }
+
+    #[test]
+    fn image_comparison_region_outliers_are_excluded_from_global_budget() {
+        // Row 0 is covered by a region with a tolerance high enough to absorb its
+        // difference entirely; row 1 is uncovered and should count against the global
+        // budget on its own.
+        let comparison = ImageComparison {
+            regions: vec![FuzzyRegion {
+                x: 0,
+                y: 0,
+                width: 1,
+                height: 1,
+                tolerance: 255,
+                max_outliers: 0,
+            }],
+            ..Default::default()
+        };
+        let actual =
+            image::RgbaImage::from_raw(1, 2, vec![0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        let expected =
+            image::RgbaImage::from_raw(1, 2, vec![255, 255, 255, 255, 255, 255, 255, 255])
+                .unwrap();
+
+        let err = comparison
+            .test(
+                "test",
+                actual,
+                expected,
+                Path::new(NO_WRITES_PATH),
+                "wgpu-warp".to_string(),
+                true,
+            )
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("the rest of the image had 4 outliers"));
+        assert!(!message.contains("region 0"));
+    }
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "ruffle_options_test_{name}_{}",
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn write_reference_frame(dir: &Path, reference_path: &Path, frame_number: u32, pixel: [u8; 4]) {
+        let frame_dir = dir.join(reference_path);
+        fs::create_dir_all(&frame_dir).unwrap();
+        solid_image(1, 1, pixel)
+            .save(frame_dir.join(format!("{frame_number:04}.png")))
+            .unwrap();
+    }
+
+    #[test]
+    fn video_comparison_succeeds_when_frames_match_reference() {
+        let temp_dir = TempDir::new("succeeds");
+        let comparison = VideoComparison {
+            start_frame: 0,
+            end_frame: 1,
+            reference_path: PathBuf::from("refs"),
+            ..Default::default()
+        };
+
+        write_reference_frame(&temp_dir.0, &comparison.reference_path, 0, [0, 0, 0, 255]);
+        write_reference_frame(&temp_dir.0, &comparison.reference_path, 1, [0, 0, 0, 255]);
+
+        let frames = vec![
+            solid_image(1, 1, [0, 0, 0, 255]),
+            solid_image(1, 1, [0, 0, 0, 255]),
+        ];
+
+        assert!(comparison.test("video", &frames, &temp_dir.0).is_ok());
+    }
+
+    #[test]
+    fn video_comparison_reports_first_diverging_frame_and_worst_frame() {
+        let temp_dir = TempDir::new("diverges");
+        let comparison = VideoComparison {
+            start_frame: 0,
+            end_frame: 2,
+            reference_path: PathBuf::from("refs"),
+            max_outliers: 0,
+            ..Default::default()
+        };
+
+        write_reference_frame(&temp_dir.0, &comparison.reference_path, 0, [0, 0, 0, 255]);
+        write_reference_frame(&temp_dir.0, &comparison.reference_path, 1, [0, 0, 0, 255]);
+        write_reference_frame(&temp_dir.0, &comparison.reference_path, 2, [0, 0, 0, 255]);
+
+        let frames = vec![
+            solid_image(1, 1, [0, 0, 0, 255]),
+            solid_image(1, 1, [10, 10, 10, 255]),
+            solid_image(1, 1, [255, 255, 255, 0]),
+        ];
+
+        let err = comparison.test("video", &frames, &temp_dir.0).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("frame 1 is the first to diverge"));
+        assert!(message.contains("worst frame Some(2)"));
+    }
+}